@@ -92,14 +92,49 @@
 // ;               Semicolon is used to start a comment; the remainder of
 //                 the line is ignored.
 
-use std::iter::Peekable;
-use std::str::Chars;
+// A `Peekable<Chars>`-like cursor over an owned `String`, advancing by
+// byte offset rather than materializing the whole input into a `Vec<char>`
+// up front (four bytes per char for typical ASCII zone data), so a `Lexer`
+// only ever holds one copy of its source text.
+struct CharCursor {
+    buf: String,
+    pos: usize,
+    peeked: Option<char>,
+}
+
+impl CharCursor {
+    fn new(buf: String) -> CharCursor {
+        CharCursor { buf, pos: 0, peeked: None }
+    }
+
+    fn peek(&mut self) -> Option<&char> {
+        if self.peeked.is_none() {
+            self.peeked = self.buf[self.pos..].chars().next();
+        }
+        self.peeked.as_ref()
+    }
 
-pub struct Lexer<'a> {
-    zf: Peekable<Chars<'a>>,
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peeked.take().or_else(|| self.buf[self.pos..].chars().next())?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+}
+
+pub struct Lexer {
+    zf: CharCursor,
     lineno: i32,
     charno: i32,
     state: State,
+    // Count of unmatched `(` seen in the current RR's RDATA. While this is
+    // above zero, a line ending is just another field delimiter instead of
+    // the end of the entry, so a wrapped SOA lexes as one continuous RR.
+    paren_depth: i32,
+    // Set once the `Iterator` impl has reached `Token::EOF` (which it does
+    // not yield) or yielded a `LexerError`, neither of which `next_token`
+    // is guaranteed to move past on a repeat call, so further iteration
+    // just stops.
+    iter_done: bool,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -117,14 +152,38 @@ pub enum Token {
         ttl: i32,
         lineno: i32,
     },
+    Generate {
+        start: i64,
+        stop: i64,
+        step: i64,
+        lhs: Vec<GeneratePart>,
+        ttl: Option<String>,
+        class: Option<String>,
+        rtype: String,
+        rhs: Vec<GeneratePart>,
+        lineno: i32,
+    },
     Text(String),
-    DomainName(String),
+    // The owner name split into its labels, escapes already decoded, so an
+    // escaped dot (`\.`) inside a label can't be confused with the dots that
+    // separate labels (see `Lexer::split_domain_labels`). An absolute name
+    // (one ending in `.`) carries a trailing empty label.
+    DomainName(Vec<String>),
     Comment,
     OpenParen,
     CloseParen,
     EOF,
 }
 
+// One piece of a `$GENERATE` lhs/rhs template: either literal text, or a
+// `$`/`${offset,width,base}` placeholder expanded against the iterator
+// value when the record is later materialized.
+#[derive(Clone, PartialEq, Debug)]
+pub enum GeneratePart {
+    Literal(String),
+    Placeholder { offset: i32, width: usize, base: char },
+}
+
 #[derive(Clone, PartialEq, Debug)]
 enum State {
     StartLine,
@@ -133,26 +192,102 @@ enum State {
     IncludeFileName,
     IncludeDomainName { file_name: String },
     Ttl,
+    Generate,
     DomainName,
     Blank,
     Comment,
     RestOfLine,
+    // Whitespace-delimited fields of an RR (TTL/class/type/RDATA), entered
+    // once the owner name (or its absence, for `State::Blank` lines) has
+    // been resolved.
+    Rdata,
     Quote,
     EOL,
     EOF,
 }
 
-impl<'a> Lexer<'a> {
+// A machine-readable category for a `LexerError`, so a caller can react to a
+// class of failure (e.g. retry, or point at the zone file) without matching
+// on `Display` text.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LexerErrorKind {
+    UnexpectedControlChar,
+    UnknownControlEntry,
+    UnexpectedEof,
+    UnbalancedParen,
+    MalformedEscape,
+    MalformedGenerate,
+    MalformedTtl,
+}
+
+// Mirrors the `ParseError`/`LexerError` split in the trust-dns sources: a
+// lexing failure carries its kind plus the position (`lineno`/`charno`) the
+// `Lexer` had reached when it gave up, so a caller can report exactly where
+// in the zone file the problem is.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LexerError {
+    pub kind: LexerErrorKind,
+    pub message: &'static str,
+    pub lineno: i32,
+    pub charno: i32,
+}
+
+impl std::fmt::Display for LexerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} (line {}, column {})", self.message, self.lineno, self.charno)
+    }
+}
+
+impl std::error::Error for LexerError {}
+
+// The error type returned by the pure/static parsing helpers below, which
+// have no `&self` to stamp a position onto. `next_token` converts this into
+// a real `LexerError` via `Lexer::error` at the one point it has `self`.
+type StaticLexerError = (LexerErrorKind, &'static str);
+
+impl Lexer {
     pub fn new(zonefile: &str) -> Lexer {
+        Self::from_string(zonefile.to_string())
+    }
+
+    // Reads the full contents of `reader` up front and lexes from that
+    // owned buffer, so callers aren't required to hold the whole zone as a
+    // `&str` themselves (e.g. when it's coming off a socket or a large
+    // file). `lineno`/`charno` bookkeeping is identical to the `&str` path,
+    // since both end up driving the same `CharCursor`. The read goes
+    // straight into the buffer `CharCursor` keeps, so (unlike `new`, which
+    // must copy its borrowed `&str`) this path never duplicates the input.
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> std::io::Result<Lexer> {
+        let mut zonefile = String::new();
+        reader.read_to_string(&mut zonefile)?;
+        Ok(Self::from_string(zonefile))
+    }
+
+    fn from_string(zonefile: String) -> Lexer {
         Lexer {
-            zf: zonefile.chars().peekable(),
+            zf: CharCursor::new(zonefile),
             lineno: 0,
             charno: 0,
             state: State::StartLine,
+            paren_depth: 0,
+            iter_done: false,
         }
     }
 
-    pub fn next_token(&mut self) -> Result<Option<Token>, &str> {
+    // Builds a `LexerError` stamped with the current position.
+    fn error(&self, kind: LexerErrorKind, message: &'static str) -> LexerError {
+        LexerError { kind, message, lineno: self.lineno, charno: self.charno }
+    }
+
+    // Applies the same escape-decoding RR owner names get to a standalone
+    // <domain-name> argument ($ORIGIN, $INCLUDE) that isn't split into
+    // labels, so e.g. `$ORIGIN a\.b.example.` doesn't silently carry an
+    // undecoded `\.` through to callers.
+    fn decode_domain_name(&self, raw: &str) -> Result<String, LexerError> {
+        Self::decode_escapes(raw).map_err(|(kind, message)| self.error(kind, message))
+    }
+
+    pub fn next_token(&mut self) -> Result<Option<Token>, LexerError> {
         let mut chars: Option<String> = None;
 
         loop {
@@ -163,7 +298,7 @@ impl<'a> Lexer<'a> {
             //    ch, self.state, chars
             //);
 
-            match self.state {
+            match &self.state {
                 State::StartLine => match ch {
                     Some('\r') | Some('\n') => {
                         self.state = State::EOL;
@@ -175,20 +310,31 @@ impl<'a> Lexer<'a> {
                     Some('$') => {
                         self.state = State::Dollar;
                         chars = Some(String::new());
-                        self.next();
+                        self.advance();
                     }
                     None => return Ok(Some(Token::EOF)),
+                    // A line beginning with whitespace has no owner name;
+                    // the RR is assumed to belong to the last stated owner.
+                    Some(ch) if ch.is_whitespace() => {
+                        self.state = State::Blank;
+                        self.advance();
+                    }
+                    Some(ch) if ch.is_control() => {
+                        return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
+                    }
+                    // Anything else starts a new owner name, resetting it.
                     Some(_) => {
-                        unimplemented!();
+                        self.state = State::DomainName;
+                        chars = Some(String::new());
                     }
                 },
                 State::Dollar => match ch {
                     Some(ch) if ch.is_control() => {
-                        return Err("Unexpected control character found");
+                        return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
                     }
                     Some(ch) if !ch.is_whitespace() => {
                         Self::push_to_str(&mut chars, *ch);
-                        self.next();
+                        self.advance();
                     }
                     Some(ch) if ch.is_whitespace() => {
                         let dollar: String = chars.take().unwrap();
@@ -199,55 +345,399 @@ impl<'a> Lexer<'a> {
                             self.state = State::Origin;
                         } else if "TTL" == dollar {
                             self.state = State::Ttl;
+                        } else if "GENERATE" == dollar {
+                            self.state = State::Generate;
                         } else {
-                            return Err("Unknown control entry");
+                            return Err(self.error(LexerErrorKind::UnknownControlEntry, "Unknown control entry"));
                         }
-                        
+
                         chars = Some(String::new());
-                        self.next();
+                        self.advance();
                     }
                     None | Some('\r') | Some('\n') | Some(_) => {
-                        return Err("Unexpected end of line");
+                        return Err(self.error(LexerErrorKind::UnexpectedEof, "Unexpected end of line"));
                     }
                 },
                 State::Origin => match ch {
                     Some(ch) if !ch.is_control() && !ch.is_whitespace() => {
                         Self::push_to_str(&mut chars, *ch);
-                        self.next();
+                        self.advance();
                     }
                     None | Some('\r') | Some('\n') | Some(_) => {
                         self.state = State::RestOfLine;
-                        let domain_name = chars.take().unwrap_or_else(|| "".into());
-                        return Ok(Some(Token::Origin { domain_name: domain_name, lineno: self.lineno }));
+                        let raw = chars.take().unwrap_or_else(|| "".into());
+                        let domain_name = self.decode_domain_name(&raw)?;
+                        return Ok(Some(Token::Origin { domain_name, lineno: self.lineno }));
+                    }
+                }
+                // `$INCLUDE file-name [domain-name]`: the file name is
+                // mandatory, the origin argument is not.
+                State::IncludeFileName => match ch {
+                    Some(ch) if !ch.is_control() && !ch.is_whitespace() => {
+                        Self::push_to_str(&mut chars, *ch);
+                        self.advance();
+                    }
+                    None | Some('\r') | Some('\n') => {
+                        self.state = State::RestOfLine;
+                        let file_name = chars.take().unwrap_or_else(|| "".into());
+                        return Ok(Some(Token::Include {
+                            file_name,
+                            domain_name: None,
+                            lineno: self.lineno,
+                        }));
+                    }
+                    Some(_) => {
+                        let file_name = chars.take().unwrap_or_else(|| "".into());
+                        self.state = State::IncludeDomainName { file_name };
+                    }
+                },
+                State::IncludeDomainName { file_name } => {
+                    let file_name = file_name.clone();
+                    match ch {
+                        None | Some('\r') | Some('\n') | Some(';') => {
+                            self.state = State::RestOfLine;
+                            let domain_name = chars
+                                .take()
+                                .map(|raw| self.decode_domain_name(&raw))
+                                .transpose()?;
+                            return Ok(Some(Token::Include {
+                                file_name,
+                                domain_name,
+                                lineno: self.lineno,
+                            }));
+                        }
+                        Some(ch) if ch.is_whitespace() => {
+                            if let Some(raw) = chars.take() {
+                                self.state = State::RestOfLine;
+                                let domain_name = self.decode_domain_name(&raw)?;
+                                return Ok(Some(Token::Include {
+                                    file_name,
+                                    domain_name: Some(domain_name),
+                                    lineno: self.lineno,
+                                }));
+                            }
+                            // still skipping the delimiter before the
+                            // optional domain-name argument starts
+                            self.advance();
+                        }
+                        Some(ch) if ch.is_control() => {
+                            return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
+                        }
+                        Some(ch) => {
+                            if chars.is_none() {
+                                chars = Some(String::new());
+                            }
+                            Self::push_to_str(&mut chars, *ch);
+                            self.advance();
+                        }
+                    }
+                }
+                // `$TTL ttl`: a bare decimal setting the default TTL for
+                // subsequent RRs that don't state their own.
+                State::Ttl => match ch {
+                    Some(ch) if !ch.is_control() && !ch.is_whitespace() => {
+                        Self::push_to_str(&mut chars, *ch);
+                        self.advance();
+                    }
+                    None | Some('\r') | Some('\n') | Some(_) => {
+                        self.state = State::RestOfLine;
+                        let raw = chars.take().unwrap_or_else(|| "".into());
+                        let ttl = raw
+                            .parse::<i32>()
+                            .map_err(|_| self.error(LexerErrorKind::MalformedTtl, "Malformed $TTL value"))?;
+                        return Ok(Some(Token::TTL { ttl, lineno: self.lineno }));
                     }
                 }
+                // `$GENERATE range lhs [ttl] [class] type rhs`: the whole
+                // directive is read in one go since it has a variable
+                // number of whitespace-delimited fields.
+                State::Generate => {
+                    let lineno = self.lineno;
+                    let fields = self.read_line_fields()?;
+                    self.state = State::RestOfLine;
+                    let token = Self::build_generate_token(fields, lineno)
+                        .map_err(|(kind, message)| self.error(kind, message))?;
+                    return Ok(Some(token));
+                }
                 State::Comment => {
                     self.state = State::RestOfLine;
                     chars = Some(String::new());
-                    self.next();
+                    self.advance();
                 }
-                State::RestOfLine => match ch {
-                    None | Some('\r') | Some('\n') => {
+                // Raw text trailing a directive: the comment body after
+                // `State::Comment`, or whatever (if anything) follows
+                // `$ORIGIN`/`$INCLUDE`/`$GENERATE`'s own arguments. Callers
+                // that have nothing to report leave `chars` as `None`, so a
+                // directive immediately followed by EOL emits no token here.
+                State::RestOfLine => {
+                    // A `;` starts a comment same as it does in `Rdata`/
+                    // `Blank`; flush any text collected so far first so the
+                    // comment marker itself isn't swallowed into it.
+                    if let Some(';') = ch {
+                        if let Some(text) = chars.take() {
+                            return Ok(Some(Token::Text(text)));
+                        }
+                        self.state = State::Comment;
+                        return Ok(Some(Token::Comment));
+                    }
+
+                    // The delimiter between a directive's own arguments and
+                    // whatever trails it (a comment, or nothing) isn't part
+                    // of either, so skip it instead of starting a `Text`
+                    // token with it. `State::Comment` primes `chars` with an
+                    // empty string before entering this state, so a real
+                    // in-progress comment body (`chars.is_some()`) still
+                    // keeps its interior whitespace verbatim.
+                    if chars.is_none() {
+                        if let Some(ch) = ch {
+                            if ch.is_whitespace() && *ch != '\r' && *ch != '\n' {
+                                self.advance();
+                                continue;
+                            }
+                        }
+                    }
+
+                    match ch {
+                        None | Some('\r') | Some('\n') => {
+                            // A comment inside a parenthesized group doesn't end
+                            // the entry either; fall back into `Rdata`, which
+                            // consumes the line ending itself and keeps reading
+                            // fields on the next physical line.
+                            self.state = if self.paren_depth > 0 {
+                                State::Rdata
+                            } else {
+                                State::EOL
+                            };
+                            if let Some(text) = chars.take() {
+                                return Ok(Some(Token::Text(text)));
+                            }
+                        }
+                        Some(ch) if ch.is_control() => {
+                            return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
+                        }
+                        Some(ch) => {
+                            if chars.is_none() {
+                                chars = Some(String::new());
+                            }
+                            Self::push_to_str(&mut chars, *ch);
+                            self.advance();
+                        }
+                    }
+                }
+                // The owner name at the start of an RR entry. Ends at the
+                // first run of whitespace or an unescaped comment marker,
+                // without consuming the terminating character so `Rdata`
+                // can see it next.
+                State::DomainName => {
+                    if let Some('\\') = ch {
+                        let mut buf = chars.take().unwrap_or_default();
+                        self.consume_escape(&mut buf)?;
+                        chars = Some(buf);
+                        continue;
+                    }
+
+                    if Self::is_field_end(ch) {
+                        self.state = State::Rdata;
+                        let domain_name = chars.take().unwrap_or_else(|| "".into());
+                        let labels = Self::split_domain_labels(&domain_name)
+                            .map_err(|(kind, message)| self.error(kind, message))?;
+                        return Ok(Some(Token::DomainName(labels)));
+                    }
+
+                    match ch {
+                        Some(ch) if ch.is_control() => {
+                            return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
+                        }
+                        Some(ch) => {
+                            Self::push_to_str(&mut chars, *ch);
+                            self.advance();
+                        }
+                        None => unreachable!(),
+                    }
+                }
+                // A line that began with whitespace: skip the leading
+                // delimiter, the RR keeps the previously stated owner.
+                State::Blank => match ch {
+                    None => return Ok(Some(Token::EOF)),
+                    Some('\r') | Some('\n') => {
                         self.state = State::EOL;
-                        return Ok(Some(Token::Text(chars.take().unwrap_or_else(|| "".into()))));
+                    }
+                    Some(';') => {
+                        self.state = State::Comment;
+                        return Ok(Some(Token::Comment));
+                    }
+                    Some(ch) if ch.is_whitespace() => {
+                        self.advance();
                     }
                     Some(ch) if ch.is_control() => {
-                        return Err("Unexpected control character found");
+                        return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
+                    }
+                    Some(_) => {
+                        self.state = State::Rdata;
+                    }
+                },
+                // The TTL/class/type/RDATA fields of an RR entry. Any run of
+                // tabs/spaces between fields is a single delimiter, so this
+                // alternates between skipping a delimiter (`chars` is still
+                // `None` for this call) and collecting one field.
+                State::Rdata => {
+                    // A field that opens with an unescaped `"` is a quoted
+                    // `<character-string>` instead of the usual run of
+                    // non-whitespace; its content (including whitespace) is
+                    // entirely handled by `State::Quote`.
+                    if chars.is_none() && ch == Some(&'"') {
+                        self.advance();
+                        self.state = State::Quote;
+                        chars = Some(String::new());
+                        continue;
+                    }
+
+                    // `(` / `)` group RDATA across line breaks. Like a
+                    // stray `;`, one ends an in-progress field first; once
+                    // `chars` is drained the paren is handled on its own.
+                    if chars.is_some() && matches!(ch, Some('(') | Some(')')) {
+                        let field = Self::decode_escapes(&chars.take().unwrap())
+                            .map_err(|(kind, message)| self.error(kind, message))?;
+                        return Ok(Some(Token::Text(field)));
+                    }
+                    if chars.is_none() && ch == Some(&'(') {
+                        self.advance();
+                        self.paren_depth += 1;
+                        return Ok(Some(Token::OpenParen));
+                    }
+                    if chars.is_none() && ch == Some(&')') {
+                        if self.paren_depth == 0 {
+                            return Err(self.error(LexerErrorKind::UnbalancedParen, "Unbalanced closing parenthesis"));
+                        }
+                        self.advance();
+                        self.paren_depth -= 1;
+                        return Ok(Some(Token::CloseParen));
+                    }
+
+                    if let Some('\\') = ch {
+                        let mut buf = chars.take().unwrap_or_default();
+                        self.consume_escape(&mut buf)?;
+                        chars = Some(buf);
+                        continue;
+                    }
+
+                    if Self::is_field_end(ch) {
+                        if let Some(field) = chars.take() {
+                            let field = Self::decode_escapes(&field)
+                                .map_err(|(kind, message)| self.error(kind, message))?;
+                            return Ok(Some(Token::Text(field)));
+                        }
+
+                        match ch {
+                            None => {
+                                if self.paren_depth > 0 {
+                                    return Err(self.error(
+                                        LexerErrorKind::UnexpectedEof,
+                                        "Unexpected end of file inside a parenthesized group",
+                                    ));
+                                }
+                                return Ok(Some(Token::EOF));
+                            }
+                            // Within a parenthesized group a line ending is
+                            // just another delimiter: consume it here (as
+                            // `State::EOL` would) and keep reading fields
+                            // instead of ending the entry.
+                            Some('\r') if self.paren_depth > 0 => {
+                                self.advance();
+                            }
+                            Some('\n') if self.paren_depth > 0 => {
+                                self.lineno += 1;
+                                self.charno = 0;
+                                self.advance();
+                            }
+                            Some('\r') | Some('\n') => {
+                                self.state = State::EOL;
+                            }
+                            Some(';') => {
+                                self.state = State::Comment;
+                                return Ok(Some(Token::Comment));
+                            }
+                            // Plain whitespace: consume one delimiter
+                            // character and keep looking for the next field.
+                            Some(_) => {
+                                self.advance();
+                            }
+                        }
+                    } else {
+                        match ch {
+                            Some(ch) if ch.is_control() => {
+                                return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
+                            }
+                            Some(ch) => {
+                                if chars.is_none() {
+                                    chars = Some(String::new());
+                                }
+                                Self::push_to_str(&mut chars, *ch);
+                                self.advance();
+                            }
+                            None => unreachable!(),
+                        }
+                    }
+                }
+                // A `"`-delimited `<character-string>`: everything up to the
+                // next unescaped `"` is literal content, including
+                // whitespace and (per RFC1035) a CRLF.
+                State::Quote => match ch {
+                    None => {
+                        return Err(self.error(
+                            LexerErrorKind::UnexpectedEof,
+                            "Unexpected end of file inside a quoted string",
+                        ))
+                    }
+                    Some('"') => {
+                        self.advance();
+                        self.state = State::Rdata;
+                        let text = chars.take().unwrap_or_default();
+                        let text = Self::decode_escapes(&text)
+                            .map_err(|(kind, message)| self.error(kind, message))?;
+                        return Ok(Some(Token::Text(text)));
+                    }
+                    Some('\\') => {
+                        let mut buf = chars.take().unwrap_or_default();
+                        self.consume_escape(&mut buf)?;
+                        chars = Some(buf);
+                    }
+                    Some('\n') => {
+                        self.lineno += 1;
+                        self.charno = 0;
+                        if chars.is_none() {
+                            chars = Some(String::new());
+                        }
+                        Self::push_to_str(&mut chars, '\n');
+                        self.advance();
+                    }
+                    Some('\r') => {
+                        if chars.is_none() {
+                            chars = Some(String::new());
+                        }
+                        Self::push_to_str(&mut chars, '\r');
+                        self.advance();
+                    }
+                    Some(ch) if ch.is_control() => {
+                        return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
                     }
                     Some(ch) => {
+                        if chars.is_none() {
+                            chars = Some(String::new());
+                        }
                         Self::push_to_str(&mut chars, *ch);
-                        self.next();
+                        self.advance();
                     }
                 },
                 State::EOL => {
                     match ch {
                         Some('\r') => {
-                            self.next();
+                            self.advance();
                         }
                         Some('\n') => {
                             self.lineno += 1;
                             self.charno = 0;
-                            self.next();
+                            self.advance();
                             self.state = State::StartLine;
                         }
                         // Shut the compiler up, _ won't ever match
@@ -263,7 +753,10 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn next(&mut self) {
+    // Advances the cursor by one character. Named distinctly from
+    // `Iterator::next` (below) so the two don't collide: this one just
+    // moves `zf`/`charno` forward, it doesn't produce a `Token`.
+    fn advance(&mut self) {
         self.zf.next();
         self.charno += 1;
     }
@@ -271,6 +764,544 @@ impl<'a> Lexer<'a> {
     fn push_to_str(chars: &mut Option<String>, ch: char) {
         chars.as_mut().unwrap().push(ch);
     }
+
+    // Consumes one `\X` or `\DDD` escape starting at the peeked backslash
+    // and appends it to `buf` verbatim (backslash and all). Keeping the
+    // escape un-decoded here means the surrounding field/domain-name scanner
+    // never has to special-case an escaped delimiter: `decode_escapes` and
+    // `split_domain_labels` resolve it later, once the whole field is in
+    // hand.
+    fn consume_escape(&mut self, buf: &mut String) -> Result<(), LexerError> {
+        buf.push('\\');
+        self.advance();
+
+        match self.zf.peek().copied() {
+            Some(d) if d.is_ascii_digit() => {
+                for _ in 0..3 {
+                    match self.zf.peek().copied() {
+                        Some(d) if d.is_ascii_digit() => {
+                            buf.push(d);
+                            self.advance();
+                        }
+                        _ => return Err(self.error(LexerErrorKind::MalformedEscape, "Malformed \\DDD escape")),
+                    }
+                }
+            }
+            Some(c) => {
+                buf.push(c);
+                self.advance();
+            }
+            None => return Err(self.error(LexerErrorKind::UnexpectedEof, "Unexpected end of line")),
+        }
+
+        Ok(())
+    }
+
+    // Resolves the `\X`/`\DDD` escapes in a field already scanned by
+    // `consume_escape` into their literal characters/octets. Has no access
+    // to the lexer's position, so failures are reported as a bare
+    // kind/message pair and stamped with a position by the caller.
+    fn decode_escapes(raw: &str) -> Result<String, StaticLexerError> {
+        let mut out = String::with_capacity(raw.len());
+        let mut chars = raw.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                out.push(ch);
+                continue;
+            }
+
+            match chars.peek() {
+                Some(d) if d.is_ascii_digit() => {
+                    let mut value: u32 = 0;
+                    for _ in 0..3 {
+                        match chars.next() {
+                            Some(d) if d.is_ascii_digit() => {
+                                value = value * 10 + d.to_digit(10).unwrap();
+                            }
+                            _ => return Err((LexerErrorKind::MalformedEscape, "Malformed \\DDD escape")),
+                        }
+                    }
+                    if value > 255 {
+                        return Err((LexerErrorKind::MalformedEscape, "\\DDD escape out of range"));
+                    }
+                    out.push(value as u8 as char);
+                }
+                Some(_) => out.push(chars.next().unwrap()),
+                None => return Err((LexerErrorKind::UnexpectedEof, "Unexpected end of escape sequence")),
+            }
+        }
+
+        Ok(out)
+    }
+
+    // Splits a raw (still-escaped) domain name into its labels on unescaped
+    // dots, decoding each label's escapes independently so `\.` embeds a
+    // literal dot in a label instead of ending it. A trailing dot (an
+    // absolute name) leaves a trailing empty label.
+    fn split_domain_labels(raw: &str) -> Result<Vec<String>, StaticLexerError> {
+        // The root name is a single empty label. Running it through the
+        // general loop below would double-count its lone `.` as both an
+        // (empty) label and the trailing "absolute name" marker pushed
+        // after the loop, yielding `["", ""]` instead of `[""]`.
+        if raw == "." {
+            return Ok(vec![String::new()]);
+        }
+
+        let mut labels = Vec::new();
+        let mut label = String::new();
+        let mut chars = raw.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => {
+                    label.push('\\');
+                    match chars.peek().copied() {
+                        Some(d) if d.is_ascii_digit() => {
+                            for _ in 0..3 {
+                                match chars.next() {
+                                    Some(d) if d.is_ascii_digit() => label.push(d),
+                                    _ => return Err((LexerErrorKind::MalformedEscape, "Malformed \\DDD escape")),
+                                }
+                            }
+                        }
+                        Some(_) => label.push(chars.next().unwrap()),
+                        None => return Err((LexerErrorKind::UnexpectedEof, "Unexpected end of escape sequence")),
+                    }
+                }
+                '.' => {
+                    labels.push(Self::decode_escapes(&label)?);
+                    label = String::new();
+                }
+                _ => label.push(ch),
+            }
+        }
+        labels.push(Self::decode_escapes(&label)?);
+
+        Ok(labels)
+    }
+
+    // True at the end of a field: EOF, end of line, or an (unescaped)
+    // comment marker all terminate a domain name or RDATA field the same
+    // way whitespace does.
+    fn is_field_end(ch: Option<&char>) -> bool {
+        match ch {
+            None | Some('\r') | Some('\n') | Some(';') => true,
+            Some(ch) => ch.is_whitespace(),
+        }
+    }
+
+    // Reads the whitespace-delimited fields of a `$GENERATE` line in one
+    // pass, stopping (without consuming) at EOF, EOL, or a comment so the
+    // caller can fall back into the normal directive/comment flow.
+    fn read_line_fields(&mut self) -> Result<Vec<String>, LexerError> {
+        let mut fields = Vec::new();
+
+        loop {
+            while matches!(self.zf.peek(), Some(ch) if ch.is_whitespace() && *ch != '\r' && *ch != '\n')
+            {
+                self.advance();
+            }
+
+            match self.zf.peek() {
+                None | Some('\r') | Some('\n') | Some(';') => break,
+                Some(ch) if ch.is_control() => {
+                    return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
+                }
+                Some(_) => {}
+            }
+
+            let mut field = String::new();
+            while let Some(ch) = self.zf.peek() {
+                if Self::is_field_end(Some(ch)) {
+                    break;
+                }
+                if ch.is_control() {
+                    return Err(self.error(LexerErrorKind::UnexpectedControlChar, "Unexpected control character found"));
+                }
+                field.push(*ch);
+                self.advance();
+            }
+            fields.push(field);
+        }
+
+        Ok(fields)
+    }
+
+    // Assembles the fields of a `$GENERATE range lhs [ttl] [class] type rhs`
+    // line into a `Token::Generate`. Has no access to the lexer's position
+    // (the whole line has already been consumed by `read_line_fields`), so
+    // failures are reported as a bare kind/message pair and stamped with a
+    // position by the caller.
+    fn build_generate_token(
+        fields: Vec<String>,
+        lineno: i32,
+    ) -> Result<Token, StaticLexerError> {
+        let mut fields = fields.into_iter();
+        let range = fields
+            .next()
+            .ok_or((LexerErrorKind::MalformedGenerate, "$GENERATE is missing a range"))?;
+        let lhs = fields
+            .next()
+            .ok_or((LexerErrorKind::MalformedGenerate, "$GENERATE is missing a lhs"))?;
+
+        let mut middle: Vec<String> = fields.collect();
+        if middle.is_empty() {
+            return Err((LexerErrorKind::MalformedGenerate, "$GENERATE is missing a type and rhs"));
+        }
+        let rhs = middle.pop().unwrap();
+        if middle.is_empty() {
+            return Err((LexerErrorKind::MalformedGenerate, "$GENERATE is missing a type"));
+        }
+
+        let (start, stop, step) = Self::parse_generate_range(&range)?;
+        let (ttl, class, rtype) = Self::classify_generate_middle(middle)?;
+
+        Ok(Token::Generate {
+            start,
+            stop,
+            step,
+            lhs: Self::parse_generate_template(&lhs)?,
+            ttl,
+            class,
+            rtype,
+            rhs: Self::parse_generate_template(&rhs)?,
+            lineno,
+        })
+    }
+
+    // Splits the fields between lhs and rhs into their optional TTL,
+    // optional class, and required type, the same way a normal RR line
+    // defaults class and TTL when they are omitted.
+    fn classify_generate_middle(
+        fields: Vec<String>,
+    ) -> Result<(Option<String>, Option<String>, String), StaticLexerError> {
+        let mut fields = fields;
+        let rtype = fields.pop().unwrap();
+
+        let mut ttl = None;
+        let mut class = None;
+        for field in fields {
+            if !field.is_empty() && field.chars().all(|c| c.is_ascii_digit()) {
+                if ttl.is_some() {
+                    return Err((LexerErrorKind::MalformedGenerate, "$GENERATE has more than one TTL field"));
+                }
+                ttl = Some(field);
+            } else if matches!(field.to_ascii_uppercase().as_str(), "IN" | "CH" | "HS" | "NONE" | "ANY")
+            {
+                if class.is_some() {
+                    return Err((LexerErrorKind::MalformedGenerate, "$GENERATE has more than one class field"));
+                }
+                class = Some(field);
+            } else {
+                return Err((LexerErrorKind::MalformedGenerate, "$GENERATE has an unrecognized TTL/class field"));
+            }
+        }
+
+        Ok((ttl, class, rtype))
+    }
+
+    // Parses a `start-stop[/step]` range, defaulting `step` to 1.
+    fn parse_generate_range(range: &str) -> Result<(i64, i64, i64), StaticLexerError> {
+        let (start_str, rest) = range
+            .split_once('-')
+            .ok_or((LexerErrorKind::MalformedGenerate, "Malformed $GENERATE range"))?;
+        let (stop_str, step_str) = match rest.split_once('/') {
+            Some((stop, step)) => (stop, Some(step)),
+            None => (rest, None),
+        };
+
+        let start: i64 = start_str
+            .parse()
+            .map_err(|_| (LexerErrorKind::MalformedGenerate, "Malformed $GENERATE range"))?;
+        let stop: i64 = stop_str
+            .parse()
+            .map_err(|_| (LexerErrorKind::MalformedGenerate, "Malformed $GENERATE range"))?;
+        let step: i64 = match step_str {
+            Some(step_str) => step_str
+                .parse()
+                .map_err(|_| (LexerErrorKind::MalformedGenerate, "Malformed $GENERATE range"))?,
+            None => 1,
+        };
+
+        if stop < start {
+            return Err((LexerErrorKind::MalformedGenerate, "$GENERATE range stop must be >= start"));
+        }
+        if step <= 0 {
+            return Err((LexerErrorKind::MalformedGenerate, "$GENERATE range step must be positive"));
+        }
+
+        Ok((start, stop, step))
+    }
+
+    // Parses a lhs/rhs template into literal runs and `$`/`${...}`
+    // placeholders. `\$` escapes a literal dollar sign.
+    fn parse_generate_template(template: &str) -> Result<Vec<GeneratePart>, StaticLexerError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' if chars.peek() == Some(&'$') => {
+                    chars.next();
+                    literal.push('$');
+                }
+                '$' => {
+                    if !literal.is_empty() {
+                        parts.push(GeneratePart::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    if chars.peek() == Some(&'{') {
+                        chars.next();
+                        let mut spec = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('}') => break,
+                                Some(c) => spec.push(c),
+                                None => {
+                                    return Err((
+                                        LexerErrorKind::MalformedGenerate,
+                                        "Unterminated ${...} in $GENERATE template",
+                                    ))
+                                }
+                            }
+                        }
+                        parts.push(Self::parse_generate_placeholder(&spec)?);
+                    } else {
+                        parts.push(GeneratePart::Placeholder {
+                            offset: 0,
+                            width: 0,
+                            base: 'd',
+                        });
+                    }
+                }
+                _ => literal.push(ch),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(GeneratePart::Literal(literal));
+        }
+
+        Ok(parts)
+    }
+
+    // Parses the body of a `${offset,width,base}` placeholder.
+    fn parse_generate_placeholder(spec: &str) -> Result<GeneratePart, StaticLexerError> {
+        let mut fields = spec.split(',');
+
+        let malformed = (LexerErrorKind::MalformedGenerate, "Malformed ${offset,width,base} in $GENERATE template");
+
+        let offset: i32 = fields
+            .next()
+            .filter(|s| !s.is_empty())
+            .ok_or(malformed)?
+            .parse()
+            .map_err(|_| malformed)?;
+
+        let width: usize = match fields.next() {
+            Some("") | None => 0,
+            Some(width_str) => width_str.parse().map_err(|_| malformed)?,
+        };
+
+        let base = match fields.next() {
+            None => 'd',
+            Some(base_str) if base_str.len() == 1 => {
+                let base = base_str.chars().next().unwrap();
+                if matches!(base, 'd' | 'o' | 'x' | 'X' | 'n') {
+                    base
+                } else {
+                    return Err((LexerErrorKind::MalformedGenerate, "Unknown $GENERATE base modifier"));
+                }
+            }
+            Some(_) => return Err((LexerErrorKind::MalformedGenerate, "Unknown $GENERATE base modifier")),
+        };
+
+        if fields.next().is_some() {
+            return Err(malformed);
+        }
+
+        Ok(GeneratePart::Placeholder { offset, width, base })
+    }
+}
+
+// Lets tokens compose with standard iterator adaptors instead of callers
+// hand-rolling `loop { next_token() }`. Ends the stream (returns `None`)
+// when `next_token` reaches `Token::EOF`; the `EOF` token itself is not
+// yielded.
+impl Iterator for Lexer {
+    type Item = Result<Token, LexerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.iter_done {
+            return None;
+        }
+
+        match self.next_token() {
+            Ok(Some(Token::EOF)) | Ok(None) => {
+                self.iter_done = true;
+                None
+            }
+            Ok(Some(token)) => Some(Ok(token)),
+            Err(e) => {
+                self.iter_done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// NSD and miekg/dns both cap $INCLUDE nesting at this depth to guard
+// against include loops.
+const MAX_INCLUDE_DEPTH: u32 = 7;
+
+#[derive(Debug)]
+pub enum DriverError {
+    Lexer(LexerError),
+    Io(std::io::Error),
+    MaxIncludeDepthExceeded { max_depth: u32 },
+    RelativeIncludeWithoutBaseDir { file_name: String },
+}
+
+impl std::fmt::Display for DriverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DriverError::Lexer(e) => write!(f, "{}", e),
+            DriverError::Io(e) => write!(f, "{}", e),
+            DriverError::MaxIncludeDepthExceeded { max_depth } => {
+                write!(f, "$INCLUDE nesting exceeded the maximum depth of {}", max_depth)
+            }
+            DriverError::RelativeIncludeWithoutBaseDir { file_name } => write!(
+                f,
+                "cannot resolve relative $INCLUDE file name '{}' without a base directory",
+                file_name
+            ),
+        }
+    }
+}
+
+// One file's worth of `$INCLUDE` nesting: the `Lexer` currently being
+// driven, and the origin to restore once that lexer reaches EOF and
+// control returns to whatever included it.
+struct IncludeFrame {
+    lexer: Lexer,
+    restore_origin: Option<String>,
+}
+
+// Drives a `Lexer` across `$INCLUDE` boundaries so that tokens from
+// included files flow as one continuous stream, as if they had been
+// copy-pasted into the parent file at the point of the `$INCLUDE`.
+// `$INCLUDE` reads the whole included file into memory and pushes a
+// frame for it onto `stack`; its tokens are then lexed on demand rather
+// than collected up front. Every frame on the stack keeps its file's full
+// text until that file reaches EOF, so memory grows with the combined
+// size of the files in the current include chain.
+pub struct Driver {
+    stack: Vec<IncludeFrame>,
+    base_dir: Option<std::path::PathBuf>,
+    origin: Option<String>,
+}
+
+impl Driver {
+    pub fn new(zonefile: &str, base_dir: Option<std::path::PathBuf>) -> Driver {
+        Driver {
+            stack: vec![IncludeFrame { lexer: Lexer::new(zonefile), restore_origin: None }],
+            base_dir,
+            origin: None,
+        }
+    }
+
+    pub fn from_reader<R: std::io::Read>(
+        reader: R,
+        base_dir: Option<std::path::PathBuf>,
+    ) -> std::io::Result<Driver> {
+        Ok(Driver {
+            stack: vec![IncludeFrame { lexer: Lexer::from_reader(reader)?, restore_origin: None }],
+            base_dir,
+            origin: None,
+        })
+    }
+
+    pub fn next_token(&mut self) -> Result<Option<Token>, DriverError> {
+        loop {
+            let frame = match self.stack.last_mut() {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+
+            match frame.lexer.next_token() {
+                Ok(Some(Token::EOF)) | Ok(None) => {
+                    // The outermost file reaching EOF ends the whole
+                    // stream; an included file reaching EOF just pops
+                    // back to whatever included it.
+                    if self.stack.len() == 1 {
+                        return Ok(Some(Token::EOF));
+                    }
+                    let frame = self.stack.pop().unwrap();
+                    self.origin = frame.restore_origin;
+                    if let Some(origin) = &self.origin {
+                        return Ok(Some(Token::Origin { domain_name: origin.clone(), lineno: 0 }));
+                    }
+                }
+                Ok(Some(Token::Include { file_name, domain_name, lineno: _ })) => {
+                    if self.stack.len() as u32 > MAX_INCLUDE_DEPTH {
+                        return Err(DriverError::MaxIncludeDepthExceeded { max_depth: MAX_INCLUDE_DEPTH });
+                    }
+
+                    let contents = read_include(&file_name, self.base_dir.as_deref())?;
+                    let restore_origin = self.origin.clone();
+                    let lexer = Lexer::new(&contents);
+
+                    // The domain-name argument to $INCLUDE sets the
+                    // origin inside the included file only; `restore_origin`
+                    // puts the parent's own origin back once it's done.
+                    match domain_name {
+                        Some(domain_name) => {
+                            self.origin = Some(domain_name.clone());
+                            self.stack.push(IncludeFrame { lexer, restore_origin });
+                            return Ok(Some(Token::Origin { domain_name, lineno: 0 }));
+                        }
+                        None => self.stack.push(IncludeFrame { lexer, restore_origin }),
+                    }
+                }
+                Ok(token) => {
+                    if let Some(token) = &token {
+                        self.track_origin(token);
+                    }
+                    return Ok(token);
+                }
+                Err(e) => return Err(DriverError::Lexer(e)),
+            }
+        }
+    }
+
+    fn track_origin(&mut self, token: &Token) {
+        if let Token::Origin { domain_name, .. } = token {
+            self.origin = Some(domain_name.clone());
+        }
+    }
+}
+
+fn read_include(
+    file_name: &str,
+    base_dir: Option<&std::path::Path>,
+) -> Result<String, DriverError> {
+    let path = std::path::Path::new(file_name);
+    let resolved = if path.is_relative() {
+        match base_dir {
+            Some(dir) => dir.join(path),
+            None => {
+                return Err(DriverError::RelativeIncludeWithoutBaseDir {
+                    file_name: file_name.to_string(),
+                })
+            }
+        }
+    } else {
+        path.to_path_buf()
+    };
+
+    std::fs::read_to_string(&resolved).map_err(DriverError::Io)
 }
 
 #[cfg(test)]
@@ -345,4 +1376,555 @@ mod tests {
         assert_eq!(lexer.next_token(), Ok(Some(Token::Comment)));
     }
 
+    #[test]
+    fn origin_decodes_escapes() {
+        let zonefile = "$ORIGIN a\\.b.example.\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Origin { domain_name: "a.b.example.".into(), lineno: 0 }))
+        );
+    }
+
+    #[test]
+    fn ttl_only() {
+        let zonefile = "$TTL 3600\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::TTL { ttl: 3600, lineno: 0 })));
+    }
+
+    #[test]
+    fn ttl_with_comment() {
+        let zonefile = "$TTL 3600 ; default TTL\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::TTL { ttl: 3600, lineno: 0 })));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Comment)));
+    }
+
+    #[test]
+    fn ttl_rejects_non_numeric_value() {
+        let zonefile = "$TTL oops\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert!(matches!(
+            lexer.next_token(),
+            Err(LexerError { kind: LexerErrorKind::MalformedTtl, .. })
+        ));
+    }
+
+    #[test]
+    fn simple_rr() {
+        let zonefile = "www  3600  IN  A  192.0.2.1\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("A".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("192.0.2.1".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::EOF)));
+    }
+
+    #[test]
+    fn blank_owner_inherits_previous() {
+        let zonefile = "www 3600 IN A 192.0.2.1\n\t3600 IN A 192.0.2.2\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("A".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("192.0.2.1".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("A".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("192.0.2.2".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::EOF)));
+    }
+
+    #[test]
+    fn rr_with_trailing_comment() {
+        let zonefile = "www 3600 IN A 192.0.2.1 ; a host\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("A".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("192.0.2.1".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Comment)));
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Text(" a host".into())))
+        );
+        assert_eq!(lexer.next_token(), Ok(Some(Token::EOF)));
+    }
+
+    #[test]
+    fn generate_basic() {
+        let zonefile = "$GENERATE 1-5 host$ 3600 IN A 192.0.2.$\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Generate {
+                start: 1,
+                stop: 5,
+                step: 1,
+                lhs: vec![
+                    GeneratePart::Literal("host".into()),
+                    GeneratePart::Placeholder { offset: 0, width: 0, base: 'd' },
+                ],
+                ttl: Some("3600".into()),
+                class: Some("IN".into()),
+                rtype: "A".into(),
+                rhs: vec![
+                    GeneratePart::Literal("192.0.2.".into()),
+                    GeneratePart::Placeholder { offset: 0, width: 0, base: 'd' },
+                ],
+                lineno: 0,
+            }))
+        );
+    }
+
+    #[test]
+    fn generate_with_step_and_offset_placeholder() {
+        let zonefile = "$GENERATE 0-255/16 ${0,3,d} IN A 192.0.2.${0,0,x}\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Generate {
+                start: 0,
+                stop: 255,
+                step: 16,
+                lhs: vec![GeneratePart::Placeholder { offset: 0, width: 3, base: 'd' }],
+                ttl: None,
+                class: Some("IN".into()),
+                rtype: "A".into(),
+                rhs: vec![
+                    GeneratePart::Literal("192.0.2.".into()),
+                    GeneratePart::Placeholder { offset: 0, width: 0, base: 'x' },
+                ],
+                lineno: 0,
+            }))
+        );
+    }
+
+    #[test]
+    fn generate_rejects_bad_range() {
+        let zonefile = "$GENERATE 5-1 host$ A 192.0.2.$\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert!(matches!(
+            lexer.next_token(),
+            Err(LexerError { kind: LexerErrorKind::MalformedGenerate, .. })
+        ));
+    }
+
+    #[test]
+    fn generate_rejects_unknown_base() {
+        let zonefile = "$GENERATE 1-5 host$ A 192.0.2.${0,0,q}\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert!(matches!(
+            lexer.next_token(),
+            Err(LexerError { kind: LexerErrorKind::MalformedGenerate, .. })
+        ));
+    }
+
+    #[test]
+    fn include_file_name_only() {
+        let zonefile = "$INCLUDE sub.zone\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Include {
+                file_name: "sub.zone".into(),
+                domain_name: None,
+                lineno: 0,
+            }))
+        );
+    }
+
+    #[test]
+    fn include_with_domain_name() {
+        let zonefile = "$INCLUDE sub.zone sub.example.\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Include {
+                file_name: "sub.zone".into(),
+                domain_name: Some("sub.example.".into()),
+                lineno: 0,
+            }))
+        );
+    }
+
+    #[test]
+    fn include_domain_name_decodes_escapes() {
+        let zonefile = "$INCLUDE sub.zone a\\.b.example.\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Include {
+                file_name: "sub.zone".into(),
+                domain_name: Some("a.b.example.".into()),
+                lineno: 0,
+            }))
+        );
+    }
+
+    #[test]
+    fn driver_inlines_included_tokens() {
+        let dir = std::env::temp_dir().join(format!(
+            "zoneparser-test-{}-{}",
+            std::process::id(),
+            "driver_inlines_included_tokens"
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("sub.zone"), "www 3600 IN A 192.0.2.1\n").unwrap();
+
+        let zonefile = "$ORIGIN example.\n$INCLUDE sub.zone\nmail 3600 IN A 192.0.2.2\n";
+        let mut driver = Driver::new(zonefile, Some(dir.clone()));
+
+        let mut tokens = Vec::new();
+        loop {
+            match driver.next_token().unwrap() {
+                Some(Token::EOF) | None => break,
+                Some(token) => tokens.push(token),
+            }
+        }
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Origin { domain_name: "example.".into(), lineno: 0 },
+                Token::DomainName(vec!["www".into()]),
+                Token::Text("3600".into()),
+                Token::Text("IN".into()),
+                Token::Text("A".into()),
+                Token::Text("192.0.2.1".into()),
+                Token::Origin { domain_name: "example.".into(), lineno: 0 },
+                Token::DomainName(vec!["mail".into()]),
+                Token::Text("3600".into()),
+                Token::Text("IN".into()),
+                Token::Text("A".into()),
+                Token::Text("192.0.2.2".into()),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn driver_rejects_relative_include_without_base_dir() {
+        let zonefile = "$INCLUDE sub.zone\n";
+        let mut driver = Driver::new(zonefile, None);
+        assert!(matches!(
+            driver.next_token(),
+            Err(DriverError::RelativeIncludeWithoutBaseDir { .. })
+        ));
+    }
+
+    #[test]
+    fn driver_rejects_include_loop_past_max_depth() {
+        let dir = std::env::temp_dir().join(format!(
+            "zoneparser-test-{}-{}",
+            std::process::id(),
+            "driver_rejects_include_loop_past_max_depth"
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        // self.zone includes itself, so every level of the chain bumps depth
+        // by one until MAX_INCLUDE_DEPTH is exceeded.
+        std::fs::write(dir.join("self.zone"), "$INCLUDE self.zone\n").unwrap();
+
+        let zonefile = "$INCLUDE self.zone\n";
+        let mut driver = Driver::new(zonefile, Some(dir.clone()));
+
+        let result = loop {
+            match driver.next_token() {
+                Ok(Some(Token::EOF)) | Ok(None) => break Ok(()),
+                Ok(Some(_)) => continue,
+                Err(e) => break Err(e),
+            }
+        };
+
+        assert!(matches!(
+            result,
+            Err(DriverError::MaxIncludeDepthExceeded { max_depth }) if max_depth == MAX_INCLUDE_DEPTH
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn driver_nested_origin_does_not_leak_back_to_parent() {
+        let dir = std::env::temp_dir().join(format!(
+            "zoneparser-test-{}-{}",
+            std::process::id(),
+            "driver_nested_origin_does_not_leak_back_to_parent"
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("sub.zone"),
+            "$ORIGIN other.\nwww 3600 IN A 192.0.2.1\n",
+        )
+        .unwrap();
+
+        let zonefile = "$ORIGIN example.\n$INCLUDE sub.zone\nmail 3600 IN A 192.0.2.2\n";
+        let mut driver = Driver::new(zonefile, Some(dir.clone()));
+
+        let mut tokens = Vec::new();
+        loop {
+            match driver.next_token().unwrap() {
+                Some(Token::EOF) | None => break,
+                Some(token) => tokens.push(token),
+            }
+        }
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Origin { domain_name: "example.".into(), lineno: 0 },
+                Token::Origin { domain_name: "other.".into(), lineno: 0 },
+                Token::DomainName(vec!["www".into()]),
+                Token::Text("3600".into()),
+                Token::Text("IN".into()),
+                Token::Text("A".into()),
+                Token::Text("192.0.2.1".into()),
+                // $INCLUDE restores the parent's own origin once the
+                // included file's tokens are exhausted, regardless of
+                // what the included file set it to.
+                Token::Origin { domain_name: "example.".into(), lineno: 0 },
+                Token::DomainName(vec!["mail".into()]),
+                Token::Text("3600".into()),
+                Token::Text("IN".into()),
+                Token::Text("A".into()),
+                Token::Text("192.0.2.2".into()),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // hickory-dns once mis-split an escaped dot onto its own label; assert
+    // `a\.b` stays a single label instead of becoming `a` and `b`.
+    #[test]
+    fn domain_name_escaped_dot_stays_in_one_label() {
+        let zonefile = "a\\.b.example. 3600 IN A 192.0.2.1\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::DomainName(vec![
+                "a.b".into(),
+                "example".into(),
+                "".into(),
+            ])))
+        );
+    }
+
+    #[test]
+    fn domain_name_ddd_octet_escape() {
+        let zonefile = "host\\.example\\032co. 3600 IN A 192.0.2.1\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::DomainName(vec![
+                "host.example co".into(),
+                "".into(),
+            ])))
+        );
+    }
+
+    // The root name is a single empty label, not two: `named.root`-style
+    // zone files use a bare `.` as the owner name for the root NS set.
+    #[test]
+    fn domain_name_root_is_a_single_empty_label() {
+        let zonefile = ". 3600 IN A 192.0.2.1\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::DomainName(vec!["".into()])))
+        );
+    }
+
+    #[test]
+    fn rdata_unquoted_text_decodes_escapes() {
+        let zonefile = "www 3600 IN TXT v\\=spf1\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("TXT".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("v=spf1".into()))));
+    }
+
+    #[test]
+    fn rdata_quoted_text_string() {
+        let zonefile = "www 3600 IN TXT \"hello world\"\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("TXT".into()))));
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Text("hello world".into())))
+        );
+        assert_eq!(lexer.next_token(), Ok(Some(Token::EOF)));
+    }
+
+    #[test]
+    fn rdata_quoted_text_escaped_quote() {
+        let zonefile = "www 3600 IN TXT \"say \\\"hi\\\"\" x\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("TXT".into()))));
+        assert_eq!(
+            lexer.next_token(),
+            Ok(Some(Token::Text("say \"hi\"".into())))
+        );
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("x".into()))));
+    }
+
+    #[test]
+    fn rdata_quoted_text_unterminated_is_error() {
+        let zonefile = "www 3600 IN TXT \"unterminated\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("TXT".into()))));
+        assert!(matches!(
+            lexer.next_token(),
+            Err(LexerError { kind: LexerErrorKind::UnexpectedEof, .. })
+        ));
+    }
+
+    #[test]
+    fn parenthesized_soa_spans_lines() {
+        let zonefile = "\
+@ IN SOA ns.example. admin.example. (
+    2023010100 ; serial
+    3600       ; refresh
+    600        ; retry
+    604800     ; expire
+    86400      ; minimum
+    )
+mail 3600 IN A 192.0.2.1
+";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["@".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("SOA".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("ns.example.".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("admin.example.".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::OpenParen)));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("2023010100".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Comment)));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text(" serial".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Comment)));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text(" refresh".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Comment)));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text(" retry".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("604800".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Comment)));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text(" expire".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("86400".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Comment)));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text(" minimum".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::CloseParen)));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["mail".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("A".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("192.0.2.1".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::EOF)));
+    }
+
+    #[test]
+    fn unbalanced_close_paren_is_error() {
+        let zonefile = "www 3600 IN A ) 192.0.2.1\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("A".into()))));
+        assert!(matches!(
+            lexer.next_token(),
+            Err(LexerError { kind: LexerErrorKind::UnbalancedParen, .. })
+        ));
+    }
+
+    #[test]
+    fn eof_inside_open_group_is_error() {
+        let zonefile = "www 3600 IN SOA ns. admin. (\n2023010100\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("SOA".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("ns.".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("admin.".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::OpenParen)));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("2023010100".into()))));
+        assert!(matches!(
+            lexer.next_token(),
+            Err(LexerError { kind: LexerErrorKind::UnexpectedEof, .. })
+        ));
+    }
+
+    #[test]
+    fn lexer_iterator_yields_tokens_then_stops_at_eof() {
+        let zonefile = "www 3600 IN A 192.0.2.1\n";
+        let tokens: Vec<Token> = Lexer::new(zonefile).map(|t| t.unwrap()).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::DomainName(vec!["www".into()]),
+                Token::Text("3600".into()),
+                Token::Text("IN".into()),
+                Token::Text("A".into()),
+                Token::Text("192.0.2.1".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lexer_iterator_surfaces_errors() {
+        let zonefile = "www 3600 IN A ) 192.0.2.1\n";
+        let results: Vec<Result<Token, LexerError>> = Lexer::new(zonefile).collect();
+        assert!(matches!(
+            results.last(),
+            Some(Err(LexerError { kind: LexerErrorKind::UnbalancedParen, .. }))
+        ));
+    }
+
+    #[test]
+    fn lexer_from_reader_matches_str_construction() {
+        let zonefile = "www 3600 IN A 192.0.2.1\n";
+        let lexer = Lexer::from_reader(zonefile.as_bytes()).unwrap();
+        let tokens: Vec<Token> = lexer.map(|t| t.unwrap()).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::DomainName(vec!["www".into()]),
+                Token::Text("3600".into()),
+                Token::Text("IN".into()),
+                Token::Text("A".into()),
+                Token::Text("192.0.2.1".into()),
+            ]
+        );
+    }
+
+    // `CharCursor` advances by byte offset rather than by `char`, so a
+    // multi-byte UTF-8 character must still be treated as a single char
+    // both when peeked and when consumed.
+    #[test]
+    fn rdata_handles_multibyte_utf8_text() {
+        let zonefile = "www 3600 IN TXT caf\u{e9}\n";
+        let mut lexer = Lexer::new(zonefile);
+        assert_eq!(lexer.next_token(), Ok(Some(Token::DomainName(vec!["www".into()]))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("3600".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("IN".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("TXT".into()))));
+        assert_eq!(lexer.next_token(), Ok(Some(Token::Text("caf\u{e9}".into()))));
+    }
 }
\ No newline at end of file